@@ -0,0 +1,7 @@
+// Copyright(C) 2021, Mysten Labs
+// SPDX-License-Identifier: Apache-2.0
+mod macros;
+pub mod rocks;
+mod traits;
+
+pub use traits::Map;
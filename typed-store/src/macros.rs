@@ -0,0 +1,62 @@
+// Copyright(C) 2021, Mysten Labs
+// SPDX-License-Identifier: Apache-2.0
+
+/// Defines a struct of `DBMap<K, V>` fields backed by one shared RocksDB
+/// database, generating:
+/// - the canonical list of column-family names (one per field, named after it)
+/// - an `open` constructor that calls `open_cf` with exactly those names
+/// - a `reopen` constructor with one `DBMap::reopen` call per field, bound
+///   to its own column family
+///
+/// so that adding a field automatically registers its column family and
+/// there is no hand-maintained, stringly-typed list to drift out of sync.
+/// Opening against a database missing one of these column families fails
+/// with `TypedStoreError::UnregisteredColumn`, from the underlying
+/// `DBMap::reopen` call.
+///
+/// ```
+/// use typed_store::reopen_store;
+/// use typed_store::Map;
+///
+/// reopen_store!(ExampleStore {
+///     names: DBMap<u32, String>,
+///     ages: DBMap<u32, u8>,
+/// });
+///
+/// let store = ExampleStore::open(tempfile::tempdir().unwrap(), None)
+///     .expect("failed to open store");
+/// store.names.insert(&1, &"alice".to_string()).unwrap();
+/// ```
+#[macro_export]
+macro_rules! reopen_store {
+    ($name:ident { $($field:ident: DBMap<$k:ty, $v:ty>),* $(,)? }) => {
+        pub struct $name {
+            $(pub $field: $crate::rocks::DBMap<$k, $v>,)*
+        }
+
+        impl $name {
+            /// The column-family names this store registers, one per field.
+            pub const COLUMN_FAMILIES: &'static [&'static str] = &[$(stringify!($field)),*];
+
+            /// Opens a fresh database at `path` with exactly this store's column families.
+            pub fn open<P: AsRef<std::path::Path>>(
+                path: P,
+                db_options: Option<rocksdb::Options>,
+            ) -> Result<Self, $crate::rocks::TypedStoreError> {
+                let rocksdb = $crate::rocks::open_cf(path, db_options, Self::COLUMN_FAMILIES)?;
+                Self::reopen(&rocksdb)
+            }
+
+            /// Reopens this store's `DBMap`s against an already-open database.
+            pub fn reopen(
+                rocksdb: &std::sync::Arc<rocksdb::DBWithThreadMode<rocksdb::MultiThreaded>>,
+            ) -> Result<Self, $crate::rocks::TypedStoreError> {
+                Ok($name {
+                    $(
+                        $field: $crate::rocks::DBMap::reopen(rocksdb, Some(stringify!($field)), None)?,
+                    )*
+                })
+            }
+        }
+    };
+}
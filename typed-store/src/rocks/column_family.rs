@@ -0,0 +1,151 @@
+// Copyright(C) 2021, Mysten Labs
+// SPDX-License-Identifier: Apache-2.0
+use rocksdb::{BlockBasedOptions, Cache, DBCompactionStyle, DBCompressionType, FifoCompactOptions};
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
+
+type RawMergeFn = dyn Fn(&[u8], Option<&[u8]>, &rocksdb::MergeOperands) -> Option<Vec<u8>>
+    + Send
+    + Sync;
+
+/// A builder for per-column-family `rocksdb::Options`, so a single DB can
+/// mix e.g. a hot small-key CF (small block cache, no compression) with a
+/// large-blob CF (shared block cache, Zstd) instead of applying one global
+/// config to every column family.
+///
+/// Pass the built options to `open_cf_descriptors` alongside the CF name.
+#[derive(Clone, Default)]
+pub struct ColumnFamilyOptions {
+    compression: Option<DBCompressionType>,
+    block_cache: Option<Cache>,
+    write_buffer_size: Option<usize>,
+    max_write_buffer_number: Option<i32>,
+    bloom_filter_bits_per_key: Option<i32>,
+    fifo_max_table_files_size: Option<u64>,
+    merge_operator: Option<(&'static str, Arc<RawMergeFn>)>,
+}
+
+impl ColumnFamilyOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the block compression algorithm used for SST blocks in this CF.
+    pub fn compression(mut self, compression: DBCompressionType) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Sets the block cache backing this CF's block-based table. Pass the
+    /// same `Cache` to multiple `ColumnFamilyOptions` to share one cache
+    /// across column families.
+    pub fn block_cache(mut self, cache: Cache) -> Self {
+        self.block_cache = Some(cache);
+        self
+    }
+
+    /// Sets the size (in bytes) of each memtable before it is flushed to an SST.
+    pub fn write_buffer_size(mut self, bytes: usize) -> Self {
+        self.write_buffer_size = Some(bytes);
+        self
+    }
+
+    /// Sets the maximum number of memtables, flushed and unflushed, kept in memory.
+    pub fn max_write_buffer_number(mut self, n: i32) -> Self {
+        self.max_write_buffer_number = Some(n);
+        self
+    }
+
+    /// Adds a bloom filter to this CF's block-based table with the given bits-per-key.
+    pub fn bloom_filter_bits_per_key(mut self, bits: i32) -> Self {
+        self.bloom_filter_bits_per_key = Some(bits);
+        self
+    }
+
+    /// Makes this column family behave as a bounded ring buffer: RocksDB's
+    /// FIFO compaction style drops the oldest SST files once the CF's total
+    /// size exceeds `max_size` bytes, rather than compacting into levels.
+    pub fn fifo_compaction(mut self, max_size: u64) -> Self {
+        self.fifo_max_table_files_size = Some(max_size);
+        self
+    }
+
+    /// Registers a typed associative merge operator for this column family,
+    /// enabling read-free accumulative updates via `DBMap::merge` instead of
+    /// a read-modify-write round trip.
+    ///
+    /// RocksDB folds operands into the existing value (or `None`, if the key
+    /// is absent) in arbitrary groupings. `merge_fn` is also used for partial
+    /// merges of consecutive operands: it is then called with `None` as the
+    /// existing value even if the key holds one, and its result is stored as
+    /// a new operand and later deserialized as `M`, so `V` must serialize as
+    /// a valid `M`. `merge_fn` must therefore be associative: folding
+    /// operands `[a, b]` in one call must produce the same result as folding
+    /// `[a]` then `[b]` in two. Both `V` and `M` must round-trip
+    /// through bincode; a stored value or operand that fails to deserialize
+    /// indicates on-disk corruption and panics the merge, the same way a
+    /// corrupt value would panic RocksDB's own C++ merge operators.
+    pub fn merge_operator<V, M>(
+        mut self,
+        name: &'static str,
+        merge_fn: impl Fn(Option<V>, &mut dyn Iterator<Item = M>) -> V + Send + Sync + 'static,
+    ) -> Self
+    where
+        V: Serialize + DeserializeOwned,
+        M: Serialize + DeserializeOwned,
+    {
+        let raw_fn = move |_key: &[u8], existing: Option<&[u8]>, operands: &rocksdb::MergeOperands| {
+            let existing: Option<V> = existing.map(|bytes| {
+                bincode::deserialize(bytes)
+                    .expect("existing value failed to deserialize in merge operator")
+            });
+            let mut operands = operands.iter().map(|bytes| {
+                bincode::deserialize::<M>(bytes)
+                    .expect("merge operand failed to deserialize in merge operator")
+            });
+            let merged = merge_fn(existing, &mut operands);
+            Some(bincode::serialize(&merged).expect("failed to serialize merged value"))
+        };
+        self.merge_operator = Some((name, Arc::new(raw_fn)));
+        self
+    }
+
+    pub(super) fn into_rocksdb_options(self) -> rocksdb::Options {
+        let mut options = rocksdb::Options::default();
+
+        if let Some(compression) = self.compression {
+            options.set_compression_type(compression);
+        }
+
+        let mut block_opts = BlockBasedOptions::default();
+        if let Some(cache) = &self.block_cache {
+            block_opts.set_block_cache(cache);
+        }
+        if let Some(bits_per_key) = self.bloom_filter_bits_per_key {
+            block_opts.set_bloom_filter(bits_per_key as f64, false);
+        }
+        options.set_block_based_table_factory(&block_opts);
+
+        if let Some(size) = self.write_buffer_size {
+            options.set_write_buffer_size(size);
+        }
+        if let Some(n) = self.max_write_buffer_number {
+            options.set_max_write_buffer_number(n);
+        }
+
+        if let Some(max_size) = self.fifo_max_table_files_size {
+            let mut fifo_opts = FifoCompactOptions::default();
+            fifo_opts.set_max_table_files_size(max_size);
+            options.set_compaction_style(DBCompactionStyle::Fifo);
+            options.set_fifo_compaction_options(&fifo_opts);
+        }
+
+        if let Some((name, raw_fn)) = self.merge_operator {
+            options.set_merge_operator_associative(name, move |key, existing, operands| {
+                raw_fn(key, existing, operands)
+            });
+        }
+
+        options
+    }
+}
@@ -0,0 +1,29 @@
+// Copyright(C) 2021, Mysten Labs
+// SPDX-License-Identifier: Apache-2.0
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TypedStoreError {
+    #[error("rocksdb error: {0}")]
+    RocksDBError(String),
+    #[error("(de)serialization error: {0}")]
+    SerializationError(String),
+    #[error("the column family {0} was not registered with the database")]
+    UnregisteredColumn(String),
+    #[error("cannot batch across databases, ensure the batch and every `DBMap` passed to it share the same underlying database")]
+    CrossDBBatch,
+    #[error("transaction conflict: a key read by this transaction was modified by another writer, retry the transaction")]
+    Conflict,
+}
+
+impl From<rocksdb::Error> for TypedStoreError {
+    fn from(err: rocksdb::Error) -> Self {
+        TypedStoreError::RocksDBError(err.to_string())
+    }
+}
+
+impl From<bincode::Error> for TypedStoreError {
+    fn from(err: bincode::Error) -> Self {
+        TypedStoreError::SerializationError(err.to_string())
+    }
+}
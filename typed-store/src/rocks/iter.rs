@@ -0,0 +1,110 @@
+// Copyright(C) 2021, Mysten Labs
+// SPDX-License-Identifier: Apache-2.0
+use bincode::Options;
+use serde::de::DeserializeOwned;
+use std::marker::PhantomData;
+
+use super::{DBRawIteratorMultiThreaded, Direction, TypedStoreError};
+
+/// An iterator over key-value pairs in a column family.
+pub struct Iter<'a, K, V> {
+    db_iter: DBRawIteratorMultiThreaded<'a>,
+    direction: Direction,
+    _phantom: PhantomData<(K, V)>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    pub(super) fn new(db_iter: DBRawIteratorMultiThreaded<'a>, direction: Direction) -> Self {
+        Self {
+            db_iter,
+            direction,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, K: DeserializeOwned, V: DeserializeOwned> Iterator for Iter<'a, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.db_iter.valid() {
+            return None;
+        }
+
+        let config = bincode::DefaultOptions::new()
+            .with_big_endian()
+            .with_fixint_encoding();
+        let key = self.db_iter.key().and_then(|k| config.deserialize(k).ok());
+        let value = self
+            .db_iter
+            .value()
+            .and_then(|v| bincode::deserialize(v).ok());
+
+        match self.direction {
+            Direction::Forward => self.db_iter.next(),
+            Direction::Reverse => self.db_iter.prev(),
+        }
+
+        key.zip(value)
+    }
+}
+
+/// An iterator over key-value pairs in a column family that surfaces
+/// RocksDB errors instead of treating them as end-of-stream.
+///
+/// `raw_iterator_cf`'s `valid()` returns `false` both when iteration has
+/// reached its bound and when the iterator hit an I/O or corruption error,
+/// so a plain `Iter` silently truncates on error. `SafeIter` checks
+/// `status()` after every advance and yields a single `Err` before
+/// terminating if it is set.
+pub struct SafeIter<'a, K, V> {
+    db_iter: DBRawIteratorMultiThreaded<'a>,
+    direction: Direction,
+    is_done: bool,
+    _phantom: PhantomData<(K, V)>,
+}
+
+impl<'a, K, V> SafeIter<'a, K, V> {
+    pub(super) fn new(db_iter: DBRawIteratorMultiThreaded<'a>, direction: Direction) -> Self {
+        Self {
+            db_iter,
+            direction,
+            is_done: false,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, K: DeserializeOwned, V: DeserializeOwned> Iterator for SafeIter<'a, K, V> {
+    type Item = Result<(K, V), TypedStoreError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None;
+        }
+
+        if !self.db_iter.valid() {
+            self.is_done = true;
+            return match self.db_iter.status() {
+                Err(err) => Some(Err(TypedStoreError::from(err))),
+                Ok(()) => None,
+            };
+        }
+
+        let config = bincode::DefaultOptions::new()
+            .with_big_endian()
+            .with_fixint_encoding();
+        let result = (|| {
+            let key = config.deserialize(self.db_iter.key().expect("checked by `valid`"))?;
+            let value = bincode::deserialize(self.db_iter.value().expect("checked by `valid`"))?;
+            Ok((key, value))
+        })();
+
+        match self.direction {
+            Direction::Forward => self.db_iter.next(),
+            Direction::Reverse => self.db_iter.prev(),
+        }
+
+        Some(result)
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright(C) 2021, Mysten Labs
+// SPDX-License-Identifier: Apache-2.0
+use bincode::Options;
+use serde::de::DeserializeOwned;
+use std::marker::PhantomData;
+
+use super::{DBRawIteratorMultiThreaded, Direction, TypedStoreError};
+
+/// An iterator over keys in a column family.
+pub struct Keys<'a, K> {
+    db_iter: DBRawIteratorMultiThreaded<'a>,
+    direction: Direction,
+    _phantom: PhantomData<K>,
+}
+
+impl<'a, K> Keys<'a, K> {
+    pub(super) fn new(db_iter: DBRawIteratorMultiThreaded<'a>, direction: Direction) -> Self {
+        Self {
+            db_iter,
+            direction,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, K: DeserializeOwned> Iterator for Keys<'a, K> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.db_iter.valid() {
+            return None;
+        }
+
+        let config = bincode::DefaultOptions::new()
+            .with_big_endian()
+            .with_fixint_encoding();
+        let key = self.db_iter.key().and_then(|k| config.deserialize(k).ok());
+
+        match self.direction {
+            Direction::Forward => self.db_iter.next(),
+            Direction::Reverse => self.db_iter.prev(),
+        }
+
+        key
+    }
+}
+
+/// A key iterator that surfaces RocksDB errors instead of treating them as
+/// end-of-stream; see `SafeIter` for why this is needed.
+pub struct SafeKeys<'a, K> {
+    db_iter: DBRawIteratorMultiThreaded<'a>,
+    direction: Direction,
+    is_done: bool,
+    _phantom: PhantomData<K>,
+}
+
+impl<'a, K> SafeKeys<'a, K> {
+    pub(super) fn new(db_iter: DBRawIteratorMultiThreaded<'a>, direction: Direction) -> Self {
+        Self {
+            db_iter,
+            direction,
+            is_done: false,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, K: DeserializeOwned> Iterator for SafeKeys<'a, K> {
+    type Item = Result<K, TypedStoreError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None;
+        }
+
+        if !self.db_iter.valid() {
+            self.is_done = true;
+            return match self.db_iter.status() {
+                Err(err) => Some(Err(TypedStoreError::from(err))),
+                Ok(()) => None,
+            };
+        }
+
+        let config = bincode::DefaultOptions::new()
+            .with_big_endian()
+            .with_fixint_encoding();
+        let result = config
+            .deserialize(self.db_iter.key().expect("checked by `valid`"))
+            .map_err(TypedStoreError::from);
+
+        match self.direction {
+            Direction::Forward => self.db_iter.next(),
+            Direction::Reverse => self.db_iter.prev(),
+        }
+
+        Some(result)
+    }
+}
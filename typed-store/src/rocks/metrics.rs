@@ -0,0 +1,128 @@
+// Copyright(C) 2021, Mysten Labs
+// SPDX-License-Identifier: Apache-2.0
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// The `DBMap` operation a `DBMetricsSink` call is reporting on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DBOperation {
+    Get,
+    MultiGet,
+    Insert,
+    Remove,
+    Iterate,
+    BatchWrite,
+    Merge,
+}
+
+/// A subset of RocksDB's per-thread `PerfContext` / IOStats counters that
+/// are cheap to report and useful for diagnosing read amplification.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PerfContextMetrics {
+    pub block_cache_hit_count: u64,
+    pub block_read_count: u64,
+    pub block_read_byte: u64,
+    pub block_read_time_ns: u64,
+    pub internal_key_skipped_count: u64,
+}
+
+/// An injectable sink for `DBMap` operation metrics, modeled after a
+/// `prometheus` counter/histogram handle so this crate does not need to
+/// depend on `prometheus` directly.
+pub trait DBMetricsSink: Send + Sync {
+    /// Reports the wall-clock latency of one operation against one column family.
+    fn report_latency(&self, cf: &str, operation: DBOperation, latency: Duration);
+
+    /// Reports sampled PerfContext counters for one operation. Only called
+    /// for the fraction of operations selected by `perf_sample_rate`.
+    fn report_perf_context(&self, cf: &str, operation: DBOperation, metrics: PerfContextMetrics);
+}
+
+/// Options controlling per-operation metrics collection for a `DBMap`.
+///
+/// Metrics are off by default. Supply `metrics_sink` to start recording
+/// operation counts and latencies, and a `perf_sample_rate` of `N` to also
+/// sample RocksDB's internal PerfContext counters for roughly 1-in-`N`
+/// operations (`0` disables sampling even if a sink is set).
+#[derive(Clone, Default)]
+pub struct DBMapOptions {
+    pub metrics_sink: Option<Arc<dyn DBMetricsSink>>,
+    pub perf_sample_rate: u32,
+}
+
+/// Per-`DBMap` metrics state: the sink to report to (if any), and a cheap
+/// shared counter used to decide whether to pay for PerfContext collection
+/// on a given call. Handles produced by `Clone` or `DBMap::batch` share the
+/// same counter, so the sample rate applies to the column family as a
+/// whole rather than per-handle.
+#[derive(Clone)]
+pub(super) struct DBMapMetrics {
+    sink: Option<Arc<dyn DBMetricsSink>>,
+    perf_sample_rate: u32,
+    sample_counter: Arc<AtomicU32>,
+}
+
+impl DBMapMetrics {
+    pub(super) fn new(options: &DBMapOptions) -> Self {
+        DBMapMetrics {
+            sink: options.metrics_sink.clone(),
+            perf_sample_rate: options.perf_sample_rate,
+            sample_counter: Arc::new(AtomicU32::new(0)),
+        }
+    }
+
+    /// Fast, common-case check for whether this call should pay for
+    /// PerfContext collection. The shared counter is not touched unless
+    /// metrics and sampling are both configured. Selection is deterministic:
+    /// every `perf_sample_rate`-th call on the counter is sampled; no random
+    /// number generator is used.
+    fn should_sample_perf_context(&self) -> bool {
+        self.sink.is_some()
+            && self.perf_sample_rate > 0
+            && self.sample_counter.fetch_add(1, Ordering::Relaxed) % self.perf_sample_rate == 0
+    }
+
+    /// Times `f` and reports its latency, optionally also enabling RocksDB's
+    /// thread-local PerfContext around the call and reporting the counters
+    /// it collected. A no-op wrapper when no sink is configured.
+    pub(super) fn measure<T>(&self, cf: &str, operation: DBOperation, f: impl FnOnce() -> T) -> T {
+        let sink = match &self.sink {
+            Some(sink) => sink,
+            None => return f(),
+        };
+
+        if !self.should_sample_perf_context() {
+            let start = Instant::now();
+            let result = f();
+            sink.report_latency(cf, operation, start.elapsed());
+            return result;
+        }
+
+        rocksdb::perf::set_perf_stats(rocksdb::perf::PerfStatsLevel::EnableTime);
+        let mut perf_context = rocksdb::perf::PerfContext::default();
+        perf_context.reset();
+
+        let start = Instant::now();
+        let result = f();
+        let latency = start.elapsed();
+
+        let metrics = PerfContextMetrics {
+            block_cache_hit_count: perf_context.metric(rocksdb::perf::PerfMetric::BlockCacheHitCount),
+            block_read_count: perf_context.metric(rocksdb::perf::PerfMetric::BlockReadCount),
+            block_read_byte: perf_context.metric(rocksdb::perf::PerfMetric::BlockReadByte),
+            block_read_time_ns: perf_context.metric(rocksdb::perf::PerfMetric::BlockReadTime),
+            internal_key_skipped_count: perf_context
+                .metric(rocksdb::perf::PerfMetric::InternalKeySkippedCount),
+        };
+        rocksdb::perf::set_perf_stats(rocksdb::perf::PerfStatsLevel::Disable);
+
+        sink.report_latency(cf, operation, latency);
+        sink.report_perf_context(cf, operation, metrics);
+        result
+    }
+}
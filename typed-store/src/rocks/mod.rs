@@ -1,18 +1,32 @@
 // Copyright(C) 2021, Mysten Labs
 // SPDX-License-Identifier: Apache-2.0
+mod column_family;
 mod errors;
 mod iter;
 mod keys;
+mod metrics;
+mod snapshot;
 mod values;
+mod transaction;
 
 use crate::traits::Map;
 use bincode::Options;
-use rocksdb::{DBWithThreadMode, MultiThreaded, WriteBatch};
+use rocksdb::{DBWithThreadMode, MultiThreaded, OptimisticTransactionDB, WriteBatch};
 use serde::{de::DeserializeOwned, Serialize};
 use std::{marker::PhantomData, path::Path, sync::Arc};
 
-use self::{iter::Iter, keys::Keys, values::Values};
+use self::{
+    iter::{Iter, SafeIter},
+    keys::{Keys, SafeKeys},
+    metrics::DBMapMetrics,
+    snapshot::RocksDBSnapshotKind,
+    values::{SafeValues, Values},
+};
+pub use column_family::ColumnFamilyOptions;
 pub use errors::TypedStoreError;
+pub use metrics::{DBMapOptions, DBMetricsSink, DBOperation, PerfContextMetrics};
+pub use snapshot::{DBMapSnapshot, RocksDBSnapshot};
+pub use transaction::DBTransaction;
 
 #[cfg(test)]
 mod tests;
@@ -20,35 +34,245 @@ mod tests;
 type DBRawIteratorMultiThreaded<'a> =
     rocksdb::DBRawIteratorWithThreadMode<'a, DBWithThreadMode<MultiThreaded>>;
 
-/// An interface to a rocksDB database, keyed by a columnfamily
+/// The direction an `Iter`/`Keys`/`Values` walks the underlying RocksDB iterator in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Reverse,
+}
+
+/// The RocksDB handle backing a `DBMap`.
+///
+/// A column family is always opened against one physical database, but that
+/// database may or may not have been opened with optimistic-transaction
+/// support: CF handles, reads, and batched writes behave identically either
+/// way, so `DBMap` stays agnostic over which one it holds and only needs a
+/// `OptimisticTransactionDB` when `transaction()` is actually called.
 #[derive(Clone, Debug)]
+pub enum RocksDB {
+    DB(Arc<DBWithThreadMode<MultiThreaded>>),
+    OptimisticTransactionDB(Arc<OptimisticTransactionDB<MultiThreaded>>),
+}
+
+impl RocksDB {
+    fn cf_handle(&self, cf: &str) -> Option<Arc<rocksdb::BoundColumnFamily<'_>>> {
+        match self {
+            RocksDB::DB(db) => db.cf_handle(cf),
+            RocksDB::OptimisticTransactionDB(db) => db.cf_handle(cf),
+        }
+    }
+
+    fn as_transaction_db(&self) -> Option<&Arc<OptimisticTransactionDB<MultiThreaded>>> {
+        match self {
+            RocksDB::DB(_) => None,
+            RocksDB::OptimisticTransactionDB(db) => Some(db),
+        }
+    }
+
+    /// True if both handles point at the same underlying database, used to
+    /// guard against batches/transactions mixing `DBMap`s from different DBs.
+    fn ptr_eq(&self, other: &RocksDB) -> bool {
+        match (self, other) {
+            (RocksDB::DB(a), RocksDB::DB(b)) => Arc::ptr_eq(a, b),
+            (RocksDB::OptimisticTransactionDB(a), RocksDB::OptimisticTransactionDB(b)) => {
+                Arc::ptr_eq(a, b)
+            }
+            _ => false,
+        }
+    }
+
+    fn get_pinned_cf(
+        &self,
+        cf: &rocksdb::BoundColumnFamily<'_>,
+        key: impl AsRef<[u8]>,
+    ) -> Result<Option<impl AsRef<[u8]>>, rocksdb::Error> {
+        match self {
+            RocksDB::DB(db) => db.get_pinned_cf(cf, key),
+            RocksDB::OptimisticTransactionDB(db) => db.get_pinned_cf(cf, key),
+        }
+    }
+
+    fn put_cf(
+        &self,
+        cf: &rocksdb::BoundColumnFamily<'_>,
+        key: impl AsRef<[u8]>,
+        value: impl AsRef<[u8]>,
+    ) -> Result<(), rocksdb::Error> {
+        match self {
+            RocksDB::DB(db) => db.put_cf(cf, key, value),
+            RocksDB::OptimisticTransactionDB(db) => db.put_cf(cf, key, value),
+        }
+    }
+
+    fn delete_cf(
+        &self,
+        cf: &rocksdb::BoundColumnFamily<'_>,
+        key: impl AsRef<[u8]>,
+    ) -> Result<(), rocksdb::Error> {
+        match self {
+            RocksDB::DB(db) => db.delete_cf(cf, key),
+            RocksDB::OptimisticTransactionDB(db) => db.delete_cf(cf, key),
+        }
+    }
+
+    fn merge_cf(
+        &self,
+        cf: &rocksdb::BoundColumnFamily<'_>,
+        key: impl AsRef<[u8]>,
+        value: impl AsRef<[u8]>,
+    ) -> Result<(), rocksdb::Error> {
+        match self {
+            RocksDB::DB(db) => db.merge_cf(cf, key, value),
+            RocksDB::OptimisticTransactionDB(db) => db.merge_cf(cf, key, value),
+        }
+    }
+
+    fn raw_iterator_cf<'b>(
+        &'b self,
+        cf: &impl rocksdb::AsColumnFamilyRef,
+    ) -> DBRawIteratorMultiThreaded<'b> {
+        match self {
+            RocksDB::DB(db) => db.raw_iterator_cf(cf),
+            // Reads outside a transaction still observe the base DB's committed state.
+            RocksDB::OptimisticTransactionDB(db) => db.raw_iterator_cf(cf),
+        }
+    }
+
+    fn raw_iterator_cf_opt<'b>(
+        &'b self,
+        cf: &impl rocksdb::AsColumnFamilyRef,
+        readopts: rocksdb::ReadOptions,
+    ) -> DBRawIteratorMultiThreaded<'b> {
+        match self {
+            RocksDB::DB(db) => db.raw_iterator_cf_opt(cf, readopts),
+            RocksDB::OptimisticTransactionDB(db) => db.raw_iterator_cf_opt(cf, readopts),
+        }
+    }
+
+    fn multi_get_cf<'b, I>(
+        &'b self,
+        keys: I,
+    ) -> Vec<Result<Option<Vec<u8>>, rocksdb::Error>>
+    where
+        I: IntoIterator<Item = (&'b Arc<rocksdb::BoundColumnFamily<'b>>, Vec<u8>)>,
+    {
+        match self {
+            RocksDB::DB(db) => db.multi_get_cf(keys),
+            RocksDB::OptimisticTransactionDB(db) => db.multi_get_cf(keys),
+        }
+    }
+
+    fn get_pinned_cf_opt(
+        &self,
+        cf: &rocksdb::BoundColumnFamily<'_>,
+        key: impl AsRef<[u8]>,
+        readopts: &rocksdb::ReadOptions,
+    ) -> Result<Option<impl AsRef<[u8]>>, rocksdb::Error> {
+        match self {
+            RocksDB::DB(db) => db.get_pinned_cf_opt(cf, key, readopts),
+            RocksDB::OptimisticTransactionDB(db) => db.get_pinned_cf_opt(cf, key, readopts),
+        }
+    }
+
+    fn multi_get_cf_opt<'b, I>(
+        &'b self,
+        keys: I,
+        readopts: &rocksdb::ReadOptions,
+    ) -> Vec<Result<Option<Vec<u8>>, rocksdb::Error>>
+    where
+        I: IntoIterator<Item = (&'b Arc<rocksdb::BoundColumnFamily<'b>>, Vec<u8>)>,
+    {
+        match self {
+            RocksDB::DB(db) => db.multi_get_cf_opt(keys, readopts),
+            RocksDB::OptimisticTransactionDB(db) => db.multi_get_cf_opt(keys, readopts),
+        }
+    }
+
+    /// Takes a consistent, point-in-time snapshot of the whole database,
+    /// usable across every column family opened against it. See
+    /// `DBMap::snapshot_at` to scope several typed maps to the same one.
+    pub fn snapshot(&self) -> RocksDBSnapshot<'_> {
+        let kind = match self {
+            RocksDB::DB(db) => RocksDBSnapshotKind::DB(db.snapshot()),
+            RocksDB::OptimisticTransactionDB(db) => {
+                RocksDBSnapshotKind::OptimisticTransactionDB(db.snapshot())
+            }
+        };
+        RocksDBSnapshot::new(self.clone(), kind)
+    }
+
+    fn write(&self, batch: WriteBatch) -> Result<(), rocksdb::Error> {
+        match self {
+            RocksDB::DB(db) => db.write(batch),
+            RocksDB::OptimisticTransactionDB(db) => db.write(batch),
+        }
+    }
+
+    fn drop_cf(&self, name: &str) -> Result<(), rocksdb::Error> {
+        match self {
+            RocksDB::DB(db) => db.drop_cf(name),
+            RocksDB::OptimisticTransactionDB(db) => db.drop_cf(name),
+        }
+    }
+
+    fn create_cf<N: AsRef<str>>(
+        &self,
+        name: N,
+        options: &rocksdb::Options,
+    ) -> Result<(), rocksdb::Error> {
+        match self {
+            RocksDB::DB(db) => db.create_cf(name, options),
+            RocksDB::OptimisticTransactionDB(db) => db.create_cf(name, options),
+        }
+    }
+}
+
+/// An interface to a rocksDB database, keyed by a columnfamily
+#[derive(Clone)]
 pub struct DBMap<K, V> {
-    pub rocksdb: Arc<rocksdb::DBWithThreadMode<MultiThreaded>>,
+    pub rocksdb: RocksDB,
     _phantom: PhantomData<fn(K) -> V>,
     // the rocksDB ColumnFamily under which the map is stored
     cf: String,
+    metrics: DBMapMetrics,
 }
 
 unsafe impl<K: Send, V: Send> Send for DBMap<K, V> {}
 
+impl<K, V> std::fmt::Debug for DBMap<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DBMap")
+            .field("rocksdb", &self.rocksdb)
+            .field("cf", &self.cf)
+            .finish()
+    }
+}
+
 impl<K, V> DBMap<K, V> {
     /// Opens a database from a path, with specific options and an optional column family.
     ///
     /// This database is used to perform operations on single column family, and parametrizes
     /// all operations in `DBBatch` when writting across column families.
+    ///
+    /// `options` controls per-operation metrics collection and is off by default; see
+    /// `DBMapOptions`. This constructor has no way to register a merge operator for
+    /// `DBMap::merge`/`DBBatch::merge_batch`; open with `open_cf_descriptors` and
+    /// `ColumnFamilyOptions::merge_operator` instead if you need one.
     pub fn open<P: AsRef<Path>>(
         path: P,
         db_options: Option<rocksdb::Options>,
         opt_cf: Option<&str>,
+        options: Option<DBMapOptions>,
     ) -> Result<Self, TypedStoreError> {
         let cf_key = opt_cf.unwrap_or(rocksdb::DEFAULT_COLUMN_FAMILY_NAME);
         let cfs = vec![cf_key];
         let rocksdb = open_cf(path, db_options, &cfs)?;
 
         Ok(DBMap {
-            rocksdb,
+            rocksdb: RocksDB::DB(rocksdb),
             _phantom: PhantomData,
             cf: cf_key.to_string(),
+            metrics: DBMapMetrics::new(&options.unwrap_or_default()),
         })
     }
 
@@ -61,12 +285,35 @@ impl<K, V> DBMap<K, V> {
     ///    /// Open the DB with all needed column families first.
     ///    let rocks = open_cf(tempdir().unwrap(), None, &["First_CF", "Second_CF"]).unwrap();
     ///    /// Attach the column families to specific maps.
-    ///    let db_cf_1 = DBMap::<u32,u32>::reopen(&rocks, Some("First_CF")).expect("Failed to open storage");
-    ///    let db_cf_2 = DBMap::<u32,u32>::reopen(&rocks, Some("Second_CF")).expect("Failed to open storage");
+    ///    let db_cf_1 = DBMap::<u32,u32>::reopen(&rocks, Some("First_CF"), None).expect("Failed to open storage");
+    ///    let db_cf_2 = DBMap::<u32,u32>::reopen(&rocks, Some("Second_CF"), None).expect("Failed to open storage");
     /// ```
     pub fn reopen(
         db: &Arc<rocksdb::DBWithThreadMode<MultiThreaded>>,
         opt_cf: Option<&str>,
+        options: Option<DBMapOptions>,
+    ) -> Result<Self, TypedStoreError> {
+        let cf_key = opt_cf
+            .unwrap_or(rocksdb::DEFAULT_COLUMN_FAMILY_NAME)
+            .to_owned();
+
+        db.cf_handle(&cf_key)
+            .ok_or_else(|| TypedStoreError::UnregisteredColumn(cf_key.clone()))?;
+
+        Ok(DBMap {
+            rocksdb: RocksDB::DB(db.clone()),
+            _phantom: PhantomData,
+            cf: cf_key,
+            metrics: DBMapMetrics::new(&options.unwrap_or_default()),
+        })
+    }
+
+    /// Reopens an optimistic-transaction-enabled database (see `open_cf_transactional`)
+    /// as a typed map operating under a specific column family, so that
+    /// `transaction()` can be used against it.
+    pub fn reopen_transactional(
+        db: &Arc<OptimisticTransactionDB<MultiThreaded>>,
+        opt_cf: Option<&str>,
     ) -> Result<Self, TypedStoreError> {
         let cf_key = opt_cf
             .unwrap_or(rocksdb::DEFAULT_COLUMN_FAMILY_NAME)
@@ -76,14 +323,27 @@ impl<K, V> DBMap<K, V> {
             .ok_or_else(|| TypedStoreError::UnregisteredColumn(cf_key.clone()))?;
 
         Ok(DBMap {
-            rocksdb: db.clone(),
+            rocksdb: RocksDB::OptimisticTransactionDB(db.clone()),
             _phantom: PhantomData,
             cf: cf_key,
+            metrics: DBMapMetrics::new(&DBMapOptions::default()),
         })
     }
 
     pub fn batch(&self) -> DBBatch {
-        DBBatch::new(&self.rocksdb)
+        DBBatch::new(&self.rocksdb, self.metrics.clone(), self.cf.clone())
+    }
+
+    /// Begins an optimistic read-modify-write transaction scoped to the same
+    /// underlying database as this map. This map itself must have been opened
+    /// via `reopen_transactional`, otherwise this returns
+    /// `TypedStoreError::UnregisteredColumn`.
+    pub fn transaction(&self) -> Result<DBTransaction<'_>, TypedStoreError> {
+        let txn_db = self
+            .rocksdb
+            .as_transaction_db()
+            .ok_or_else(|| TypedStoreError::UnregisteredColumn(self.cf.clone()))?;
+        Ok(DBTransaction::new(txn_db))
     }
 
     fn cf(&self) -> Arc<rocksdb::BoundColumnFamily<'_>> {
@@ -93,6 +353,147 @@ impl<K, V> DBMap<K, V> {
     }
 }
 
+impl<K: Serialize, V> DBMap<K, V> {
+    /// Folds `partial` into the value at `key` using this column family's
+    /// merge operator, without reading the existing value first.
+    ///
+    /// The column family must have been opened with a merge operator
+    /// registered via `ColumnFamilyOptions::merge_operator` for `M`,
+    /// otherwise RocksDB rejects the merge and this returns
+    /// `TypedStoreError::RocksDBError`.
+    pub fn merge<M: Serialize>(&self, key: &K, partial: &M) -> Result<(), TypedStoreError> {
+        let config = bincode::DefaultOptions::new()
+            .with_big_endian()
+            .with_fixint_encoding();
+        let key_buf = config.serialize(key)?;
+        let operand_buf = bincode::serialize(partial)?;
+
+        self.metrics.measure(&self.cf, DBOperation::Merge, || {
+            self.rocksdb.merge_cf(&self.cf(), &key_buf, &operand_buf)
+        })?;
+        Ok(())
+    }
+}
+
+impl<'a, K, V> DBMap<K, V> {
+    /// Takes a fresh, consistent point-in-time snapshot scoped to this map's
+    /// column family. To observe the same moment from several `DBMap`s, take
+    /// one snapshot via `RocksDB::snapshot` and bind each map to it with
+    /// `snapshot_at` instead.
+    pub fn snapshot(&'a self) -> DBMapSnapshot<'a, K, V> {
+        let snapshot = Arc::new(self.rocksdb.snapshot());
+        DBMapSnapshot::new(&self.rocksdb, snapshot, self.cf.clone())
+    }
+
+    /// Scopes this map to an existing `RocksDBSnapshot`, so that it and any
+    /// other `DBMap`s scoped to the same (`Arc`-shared) snapshot observe the
+    /// same point in time even though they read different column families.
+    ///
+    /// Fails with `TypedStoreError::CrossDBBatch` if `snapshot` was taken
+    /// from a different underlying database than this map.
+    pub fn snapshot_at(
+        &'a self,
+        snapshot: Arc<RocksDBSnapshot<'a>>,
+    ) -> Result<DBMapSnapshot<'a, K, V>, TypedStoreError> {
+        if !self.rocksdb.ptr_eq(snapshot.source()) {
+            return Err(TypedStoreError::CrossDBBatch);
+        }
+        Ok(DBMapSnapshot::new(&self.rocksdb, snapshot, self.cf.clone()))
+    }
+}
+
+impl<'a, K: Serialize, V: DeserializeOwned> DBMap<K, V> {
+    /// Returns an iterator visiting the key-value pairs with keys in
+    /// `[from, to)`, in ascending key order.
+    ///
+    /// `DBMap` serializes keys with bincode's big-endian fixint encoding, so
+    /// this is only correct for `K`s whose serialized byte order matches
+    /// their logical order (fixed-width unsigned integers, signed integers
+    /// only while non-negative, and tuples/structs built from them) -- e.g.
+    /// not negative signed integers, `String` or a `Vec<T>` of varying length.
+    pub fn iter_range(&'a self, from: &K, to: &K) -> Result<Iter<'a, K, V>, TypedStoreError> {
+        let config = bincode::DefaultOptions::new()
+            .with_big_endian()
+            .with_fixint_encoding();
+        let from_buf = config.serialize(from)?;
+        let to_buf = config.serialize(to)?;
+
+        self.metrics.measure(&self.cf, DBOperation::Iterate, || {
+            let mut readopts = rocksdb::ReadOptions::default();
+            readopts.set_iterate_lower_bound(from_buf.clone());
+            readopts.set_iterate_upper_bound(to_buf);
+
+            let mut db_iter = self.rocksdb.raw_iterator_cf_opt(&self.cf(), readopts);
+            db_iter.seek(from_buf);
+
+            Ok(Iter::new(db_iter, Direction::Forward))
+        })
+    }
+
+    /// Returns an iterator seeked to `start` and walking in `direction`.
+    ///
+    /// As with `iter_range`, only keys whose serialized byte order matches
+    /// their logical order can be seeked to meaningfully.
+    pub fn iter_from(
+        &'a self,
+        start: &K,
+        direction: Direction,
+    ) -> Result<Iter<'a, K, V>, TypedStoreError> {
+        let config = bincode::DefaultOptions::new()
+            .with_big_endian()
+            .with_fixint_encoding();
+        let start_buf = config.serialize(start)?;
+
+        self.metrics.measure(&self.cf, DBOperation::Iterate, || {
+            let mut db_iter = self.rocksdb.raw_iterator_cf(&self.cf());
+            match direction {
+                Direction::Forward => db_iter.seek(start_buf),
+                Direction::Reverse => db_iter.seek_for_prev(start_buf),
+            }
+
+            Ok(Iter::new(db_iter, direction))
+        })
+    }
+}
+
+impl<'a, K, V> DBMap<K, V> {
+    /// Returns a "safe" iterator visiting each key-value pair in the map,
+    /// yielding `Err` once (and then terminating) if the underlying RocksDB
+    /// iterator hit an I/O or corruption error rather than its natural end.
+    ///
+    /// Prefer this over `iter` for scans backing consensus/ledger state,
+    /// where silently truncating on a transient read error can corrupt
+    /// derived state.
+    pub fn safe_iter(&'a self) -> SafeIter<'a, K, V> {
+        self.metrics.measure(&self.cf, DBOperation::Iterate, || {
+            let mut db_iter = self.rocksdb.raw_iterator_cf(&self.cf());
+            db_iter.seek_to_first();
+
+            SafeIter::new(db_iter, Direction::Forward)
+        })
+    }
+
+    /// Safe variant of `keys`; see `safe_iter`.
+    pub fn safe_keys(&'a self) -> SafeKeys<'a, K> {
+        self.metrics.measure(&self.cf, DBOperation::Iterate, || {
+            let mut db_iter = self.rocksdb.raw_iterator_cf(&self.cf());
+            db_iter.seek_to_first();
+
+            SafeKeys::new(db_iter, Direction::Forward)
+        })
+    }
+
+    /// Safe variant of `values`; see `safe_iter`.
+    pub fn safe_values(&'a self) -> SafeValues<'a, V> {
+        self.metrics.measure(&self.cf, DBOperation::Iterate, || {
+            let mut db_iter = self.rocksdb.raw_iterator_cf(&self.cf());
+            db_iter.seek_to_first();
+
+            SafeValues::new(db_iter, Direction::Forward)
+        })
+    }
+}
+
 /// Provides a mutable struct to form a collection of database write operations, and execute them.
 ///
 /// Batching write and delete operations is faster than performing them one by one and ensures their atomicity,
@@ -108,11 +509,11 @@ impl<K, V> DBMap<K, V> {
 /// use typed_store::Map;
 /// let rocks = open_cf(tempfile::tempdir().unwrap(), None, &["First_CF", "Second_CF"]).unwrap();
 ///
-/// let db_cf_1 = DBMap::reopen(&rocks, Some("First_CF"))
+/// let db_cf_1 = DBMap::reopen(&rocks, Some("First_CF"), None)
 ///     .expect("Failed to open storage");
 /// let keys_vals_1 = (1..100).map(|i| (i, i.to_string()));
 ///
-/// let db_cf_2 = DBMap::reopen(&rocks, Some("Second_CF"))
+/// let db_cf_2 = DBMap::reopen(&rocks, Some("Second_CF"), None)
 ///     .expect("Failed to open storage");
 /// let keys_vals_2 = (1000..1100).map(|i| (i, i.to_string()));
 ///
@@ -136,24 +537,37 @@ impl<K, V> DBMap<K, V> {
 /// ```
 ///
 pub struct DBBatch {
-    rocksdb: Arc<rocksdb::DBWithThreadMode<MultiThreaded>>,
+    rocksdb: RocksDB,
     batch: WriteBatch,
+    metrics: DBMapMetrics,
+    // the column family of the `DBMap` this batch was created from, used to
+    // attribute `DBOperation::BatchWrite` metrics.
+    cf: String,
 }
 
 impl DBBatch {
     /// Create a new batch associated with a DB reference.
     ///
-    /// Use `open_cf` to get the DB reference or an existing open database.
-    pub fn new(dbref: &Arc<rocksdb::DBWithThreadMode<MultiThreaded>>) -> Self {
+    /// Use `DBMap::batch` to construct one, so its metrics are attributed to
+    /// the right column family.
+    pub(super) fn new(dbref: &RocksDB, metrics: DBMapMetrics, cf: String) -> Self {
         DBBatch {
             rocksdb: dbref.clone(),
             batch: WriteBatch::default(),
+            metrics,
+            cf,
         }
     }
 
     /// Consume the batch and write its operations to the database
     pub fn write(self) -> Result<(), TypedStoreError> {
-        self.rocksdb.write(self.batch)?;
+        let DBBatch {
+            rocksdb,
+            batch,
+            metrics,
+            cf,
+        } = self;
+        metrics.measure(&cf, DBOperation::BatchWrite, || rocksdb.write(batch))?;
         Ok(())
     }
 }
@@ -166,7 +580,7 @@ impl DBBatch {
         db: &DBMap<K, V>,
         purged_vals: T,
     ) -> Result<Self, TypedStoreError> {
-        if !Arc::ptr_eq(&db.rocksdb, &self.rocksdb) {
+        if !db.rocksdb.ptr_eq(&self.rocksdb) {
             return Err(TypedStoreError::CrossDBBatch);
         }
 
@@ -191,7 +605,7 @@ impl DBBatch {
         from: &K,
         to: &K,
     ) -> Result<Self, TypedStoreError> {
-        if !Arc::ptr_eq(&db.rocksdb, &self.rocksdb) {
+        if !db.rocksdb.ptr_eq(&self.rocksdb) {
             return Err(TypedStoreError::CrossDBBatch);
         }
 
@@ -214,7 +628,7 @@ impl DBBatch {
         db: &DBMap<K, V>,
         new_vals: T,
     ) -> Result<Self, TypedStoreError> {
-        if !Arc::ptr_eq(&db.rocksdb, &self.rocksdb) {
+        if !db.rocksdb.ptr_eq(&self.rocksdb) {
             return Err(TypedStoreError::CrossDBBatch);
         }
 
@@ -231,6 +645,32 @@ impl DBBatch {
             .collect::<Result<_, TypedStoreError>>()?;
         Ok(self)
     }
+
+    /// Batches a set of merge operands, one per `(key, operand)` pair; a key
+    /// need not already hold a value. See `DBMap::merge`.
+    #[allow(clippy::map_collect_result_unit)] // we don't want a mutable argument
+    pub fn merge_batch<K: Serialize, V, M: Serialize, T: Iterator<Item = (K, M)>>(
+        mut self,
+        db: &DBMap<K, V>,
+        new_vals: T,
+    ) -> Result<Self, TypedStoreError> {
+        if !db.rocksdb.ptr_eq(&self.rocksdb) {
+            return Err(TypedStoreError::CrossDBBatch);
+        }
+
+        let config = bincode::DefaultOptions::new()
+            .with_big_endian()
+            .with_fixint_encoding();
+        new_vals
+            .map(|(ref k, ref m)| {
+                let k_buf = config.serialize(k)?;
+                let m_buf = bincode::serialize(m)?;
+                self.batch.merge_cf(&db.cf(), k_buf, m_buf);
+                Ok(())
+            })
+            .collect::<Result<_, TypedStoreError>>()?;
+        Ok(self)
+    }
 }
 
 impl<'a, K, V> Map<'a, K, V> for DBMap<K, V>
@@ -253,7 +693,11 @@ where
             .with_fixint_encoding();
 
         let key_buf = config.serialize(key)?;
-        let res = self.rocksdb.get_pinned_cf(&self.cf(), &key_buf)?;
+        let res = self
+            .metrics
+            .measure(&self.cf, DBOperation::Get, || {
+                self.rocksdb.get_pinned_cf(&self.cf(), &key_buf)
+            })?;
         match res {
             Some(data) => Ok(Some(bincode::deserialize(&data)?)),
             None => Ok(None),
@@ -268,7 +712,9 @@ where
         let key_buf = config.serialize(key)?;
         let value_buf = bincode::serialize(value)?;
 
-        let _ = self.rocksdb.put_cf(&self.cf(), &key_buf, &value_buf)?;
+        self.metrics.measure(&self.cf, DBOperation::Insert, || {
+            self.rocksdb.put_cf(&self.cf(), &key_buf, &value_buf)
+        })?;
         Ok(())
     }
 
@@ -278,7 +724,9 @@ where
             .with_fixint_encoding();
         let key_buf = config.serialize(key)?;
 
-        let _ = self.rocksdb.delete_cf(&self.cf(), &key_buf)?;
+        self.metrics.measure(&self.cf, DBOperation::Remove, || {
+            self.rocksdb.delete_cf(&self.cf(), &key_buf)
+        })?;
         Ok(())
     }
 
@@ -290,24 +738,30 @@ where
     }
 
     fn iter(&'a self) -> Self::Iterator {
-        let mut db_iter = self.rocksdb.raw_iterator_cf(&self.cf());
-        db_iter.seek_to_first();
+        self.metrics.measure(&self.cf, DBOperation::Iterate, || {
+            let mut db_iter = self.rocksdb.raw_iterator_cf(&self.cf());
+            db_iter.seek_to_first();
 
-        Iter::new(db_iter)
+            Iter::new(db_iter, Direction::Forward)
+        })
     }
 
     fn keys(&'a self) -> Self::Keys {
-        let mut db_iter = self.rocksdb.raw_iterator_cf(&self.cf());
-        db_iter.seek_to_first();
+        self.metrics.measure(&self.cf, DBOperation::Iterate, || {
+            let mut db_iter = self.rocksdb.raw_iterator_cf(&self.cf());
+            db_iter.seek_to_first();
 
-        Keys::new(db_iter)
+            Keys::new(db_iter, Direction::Forward)
+        })
     }
 
     fn values(&'a self) -> Self::Values {
-        let mut db_iter = self.rocksdb.raw_iterator_cf(&self.cf());
-        db_iter.seek_to_first();
+        self.metrics.measure(&self.cf, DBOperation::Iterate, || {
+            let mut db_iter = self.rocksdb.raw_iterator_cf(&self.cf());
+            db_iter.seek_to_first();
 
-        Values::new(db_iter)
+            Values::new(db_iter, Direction::Forward)
+        })
     }
 
     /// Returns a vector of values corresponding to the keys provided.
@@ -322,8 +776,13 @@ where
             .iter()
             .map(|k| Ok((&cf, config.serialize(k)?)))
             .collect();
+        let keys_bytes = keys_bytes?;
 
-        let results = self.rocksdb.multi_get_cf(keys_bytes?);
+        let results = self
+            .metrics
+            .measure(&self.cf, DBOperation::MultiGet, || {
+                self.rocksdb.multi_get_cf(keys_bytes)
+            });
 
         let values_parsed: Result<Vec<_>, TypedStoreError> = results
             .into_iter()
@@ -338,6 +797,10 @@ where
 }
 
 /// Opens a database with options, and a number of column families that are created if they do not exist.
+///
+/// Column families are opened with default options, so this has no way to
+/// register a merge operator; use `open_cf_descriptors` with
+/// `ColumnFamilyOptions::merge_operator` for that.
 pub fn open_cf<P: AsRef<Path>>(
     path: P,
     db_options: Option<rocksdb::Options>,
@@ -368,4 +831,81 @@ pub fn open_cf<P: AsRef<Path>>(
         )?)
     };
     Ok(rocksdb)
+}
+
+/// Opens a database with optimistic-transaction support, so that `DBMap`s
+/// reopened against it (via `DBMap::reopen_transactional`) can call
+/// `transaction()`. Column families behave exactly as with `open_cf`.
+pub fn open_cf_transactional<P: AsRef<Path>>(
+    path: P,
+    db_options: Option<rocksdb::Options>,
+    opt_cfs: &[&str],
+) -> Result<Arc<OptimisticTransactionDB<MultiThreaded>>, TypedStoreError> {
+    let mut options = db_options.unwrap_or_default();
+    let mut cfs = rocksdb::DBWithThreadMode::<MultiThreaded>::list_cf(&options, &path)
+        .ok()
+        .unwrap_or_default();
+
+    for cf_key in opt_cfs.iter() {
+        let key = (*cf_key).to_owned();
+        if !cfs.contains(&key) {
+            cfs.push(key);
+        }
+    }
+
+    let primary = path.as_ref().to_path_buf();
+
+    options.create_if_missing(true);
+    options.create_missing_column_families(true);
+    let rocksdb = Arc::new(OptimisticTransactionDB::<MultiThreaded>::open_cf(
+        &options, &primary, &cfs,
+    )?);
+    Ok(rocksdb)
+}
+
+/// Opens a database like `open_cf`, but lets each column family be tuned
+/// independently (compression, block cache, write buffers, bloom filters,
+/// FIFO compaction) instead of sharing one global `rocksdb::Options`.
+///
+/// `DBMap::reopen` keeps working unchanged against the returned handle.
+pub fn open_cf_descriptors<P: AsRef<Path>>(
+    path: P,
+    db_options: Option<rocksdb::Options>,
+    cfs: &[(&str, ColumnFamilyOptions)],
+) -> Result<Arc<rocksdb::DBWithThreadMode<MultiThreaded>>, TypedStoreError> {
+    let mut options = db_options.unwrap_or_default();
+    options.create_if_missing(true);
+    options.create_missing_column_families(true);
+
+    let mut descriptors: Vec<_> = cfs
+        .iter()
+        .map(|(name, cf_options)| {
+            rocksdb::ColumnFamilyDescriptor::new(*name, cf_options.clone().into_rocksdb_options())
+        })
+        .collect();
+
+    // RocksDB requires every column family already on disk to be named at
+    // open time, so -- like `open_cf` -- fold in any that aren't among the
+    // tuned `cfs` with default options, rather than failing to open.
+    let existing_cfs = rocksdb::DBWithThreadMode::<MultiThreaded>::list_cf(&options, &path)
+        .ok()
+        .unwrap_or_default();
+    for cf_name in existing_cfs {
+        if !cfs.iter().any(|(name, _)| *name == cf_name) {
+            descriptors.push(rocksdb::ColumnFamilyDescriptor::new(
+                cf_name,
+                ColumnFamilyOptions::new().into_rocksdb_options(),
+            ));
+        }
+    }
+
+    let primary = path.as_ref().to_path_buf();
+    let rocksdb = Arc::new(
+        rocksdb::DBWithThreadMode::<MultiThreaded>::open_cf_descriptors(
+            &options,
+            &primary,
+            descriptors,
+        )?,
+    );
+    Ok(rocksdb)
 }
\ No newline at end of file
@@ -0,0 +1,145 @@
+// Copyright(C) 2021, Mysten Labs
+// SPDX-License-Identifier: Apache-2.0
+use bincode::Options;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{marker::PhantomData, sync::Arc};
+
+use super::{iter::Iter, Direction, RocksDB, TypedStoreError};
+
+/// A RocksDB snapshot of an entire database, taken via `RocksDB::snapshot`.
+///
+/// Captures a consistent point-in-time view across every column family of
+/// the database it was taken from. Scope a `DBMap` to it with
+/// `DBMap::snapshot_at` -- passing the same (`Arc`-shared) snapshot to
+/// several maps lets them all observe the same moment even though they read
+/// different column families. Borrows the `RocksDB` it was taken from, so
+/// it cannot outlive the database; also keeps a cheap clone of that handle
+/// so `snapshot_at` can verify the map reading it shares the same database.
+pub struct RocksDBSnapshot<'a> {
+    source: RocksDB,
+    snapshot: RocksDBSnapshotKind<'a>,
+}
+
+pub(super) enum RocksDBSnapshotKind<'a> {
+    DB(rocksdb::SnapshotWithThreadMode<'a, rocksdb::DBWithThreadMode<rocksdb::MultiThreaded>>),
+    OptimisticTransactionDB(
+        rocksdb::SnapshotWithThreadMode<
+            'a,
+            rocksdb::OptimisticTransactionDB<rocksdb::MultiThreaded>,
+        >,
+    ),
+}
+
+impl<'a> RocksDBSnapshot<'a> {
+    pub(super) fn new(source: RocksDB, snapshot: RocksDBSnapshotKind<'a>) -> Self {
+        Self { source, snapshot }
+    }
+
+    /// The database this snapshot was taken from, for `snapshot_at`'s
+    /// cross-database guard.
+    pub(super) fn source(&self) -> &RocksDB {
+        &self.source
+    }
+
+    fn set_read_options(&self, readopts: &mut rocksdb::ReadOptions) {
+        match &self.snapshot {
+            RocksDBSnapshotKind::DB(snapshot) => readopts.set_snapshot(snapshot),
+            RocksDBSnapshotKind::OptimisticTransactionDB(snapshot) => {
+                readopts.set_snapshot(snapshot)
+            }
+        }
+    }
+}
+
+/// A consistent, point-in-time view of one column family.
+///
+/// Obtained via `DBMap::snapshot` (a fresh snapshot scoped to just this map)
+/// or `DBMap::snapshot_at` (scoping this map to a `RocksDBSnapshot` shared
+/// with other maps). Writes made after the snapshot was taken are not
+/// visible through it. Borrows its `DBMap`'s database, so it cannot outlive
+/// the database backing it.
+pub struct DBMapSnapshot<'a, K, V> {
+    rocksdb: &'a RocksDB,
+    snapshot: Arc<RocksDBSnapshot<'a>>,
+    cf: String,
+    _phantom: PhantomData<(K, V)>,
+}
+
+impl<'a, K, V> DBMapSnapshot<'a, K, V> {
+    pub(super) fn new(rocksdb: &'a RocksDB, snapshot: Arc<RocksDBSnapshot<'a>>, cf: String) -> Self {
+        Self {
+            rocksdb,
+            snapshot,
+            cf,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn cf(&self) -> Arc<rocksdb::BoundColumnFamily<'_>> {
+        self.rocksdb
+            .cf_handle(&self.cf)
+            .expect("Map-keying column family should have been checked at DB creation")
+    }
+
+    fn read_options(&self) -> rocksdb::ReadOptions {
+        let mut readopts = rocksdb::ReadOptions::default();
+        self.snapshot.set_read_options(&mut readopts);
+        readopts
+    }
+}
+
+impl<'a, K: Serialize, V: DeserializeOwned> DBMapSnapshot<'a, K, V> {
+    /// Reads `key` as it was when this snapshot was taken.
+    pub fn get(&self, key: &K) -> Result<Option<V>, TypedStoreError> {
+        let config = bincode::DefaultOptions::new()
+            .with_big_endian()
+            .with_fixint_encoding();
+        let key_buf = config.serialize(key)?;
+
+        let res = self
+            .rocksdb
+            .get_pinned_cf_opt(&self.cf(), &key_buf, &self.read_options())?;
+        match res {
+            Some(data) => Ok(Some(bincode::deserialize(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads `keys` as they were when this snapshot was taken.
+    pub fn multi_get(&self, keys: &[K]) -> Result<Vec<Option<V>>, TypedStoreError> {
+        let config = bincode::DefaultOptions::new()
+            .with_big_endian()
+            .with_fixint_encoding();
+
+        let cf = self.cf();
+        let keys_bytes: Result<Vec<_>, TypedStoreError> = keys
+            .iter()
+            .map(|k| Ok((&cf, config.serialize(k)?)))
+            .collect();
+        let keys_bytes = keys_bytes?;
+
+        let results = self
+            .rocksdb
+            .multi_get_cf_opt(keys_bytes, &self.read_options());
+
+        results
+            .into_iter()
+            .map(|value_byte| match value_byte? {
+                Some(data) => Ok(Some(bincode::deserialize(&data)?)),
+                None => Ok(None),
+            })
+            .collect()
+    }
+}
+
+impl<'a, K: DeserializeOwned, V: DeserializeOwned> DBMapSnapshot<'a, K, V> {
+    /// Iterates the whole column family as it was when this snapshot was taken.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut db_iter = self
+            .rocksdb
+            .raw_iterator_cf_opt(&self.cf(), self.read_options());
+        db_iter.seek_to_first();
+
+        Iter::new(db_iter, Direction::Forward)
+    }
+}
@@ -0,0 +1,387 @@
+// Copyright(C) 2021, Mysten Labs
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use crate::Map;
+
+fn temp_db() -> DBMap<i32, String> {
+    let path = tempfile::tempdir().unwrap();
+    DBMap::open(path, None, None, None).expect("failed to open test database")
+}
+
+#[test]
+fn test_open() {
+    let _ = temp_db();
+}
+
+#[test]
+fn test_insert_get_remove() {
+    let db = temp_db();
+    assert_eq!(db.get(&1).unwrap(), None);
+
+    db.insert(&1, &"hello".to_string()).unwrap();
+    assert_eq!(db.get(&1).unwrap(), Some("hello".to_string()));
+    assert!(db.contains_key(&1).unwrap());
+
+    db.remove(&1).unwrap();
+    assert_eq!(db.get(&1).unwrap(), None);
+}
+
+#[test]
+fn test_multi_get() {
+    let db = temp_db();
+    db.insert(&1, &"one".to_string()).unwrap();
+    db.insert(&2, &"two".to_string()).unwrap();
+
+    let values = db.multi_get(&[1, 2, 3]).unwrap();
+    assert_eq!(
+        values,
+        vec![Some("one".to_string()), Some("two".to_string()), None]
+    );
+}
+
+#[test]
+fn test_batch_write() {
+    let db = temp_db();
+    let batch = db
+        .batch()
+        .insert_batch(&db, (0..10).map(|i| (i, i.to_string())))
+        .expect("failed to batch insert");
+    batch.write().expect("failed to write batch");
+
+    for i in 0..10 {
+        assert_eq!(db.get(&i).unwrap(), Some(i.to_string()));
+    }
+}
+
+#[test]
+fn test_iter() {
+    let db = temp_db();
+    for i in 0..5 {
+        db.insert(&i, &i.to_string()).unwrap();
+    }
+
+    let mut collected: Vec<_> = db.iter().collect();
+    collected.sort();
+    assert_eq!(
+        collected,
+        (0..5).map(|i| (i, i.to_string())).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_clear() {
+    let db = temp_db();
+    db.insert(&1, &"one".to_string()).unwrap();
+    db.clear().unwrap();
+    assert_eq!(db.get(&1).unwrap(), None);
+}
+
+fn temp_transactional_db() -> DBMap<i32, String> {
+    let path = tempfile::tempdir().unwrap();
+    let rocksdb = open_cf_transactional(path, None, &[rocksdb::DEFAULT_COLUMN_FAMILY_NAME])
+        .expect("failed to open transactional test database");
+    DBMap::reopen_transactional(&rocksdb, None).expect("failed to reopen transactional map")
+}
+
+#[test]
+fn test_transaction_commit() {
+    let db = temp_transactional_db();
+    db.insert(&1, &"one".to_string()).unwrap();
+
+    let txn = db.transaction().unwrap();
+    assert_eq!(txn.get(&db, &1).unwrap(), Some("one".to_string()));
+    txn.insert(&db, &1, &"uno".to_string()).unwrap();
+    txn.commit().unwrap();
+
+    assert_eq!(db.get(&1).unwrap(), Some("uno".to_string()));
+}
+
+#[test]
+fn test_transaction_conflict() {
+    let db = temp_transactional_db();
+    db.insert(&1, &"one".to_string()).unwrap();
+
+    let txn = db.transaction().unwrap();
+    assert_eq!(txn.get(&db, &1).unwrap(), Some("one".to_string()));
+
+    // A concurrent writer touches the key the transaction already read.
+    db.insert(&1, &"conflicting write".to_string()).unwrap();
+
+    txn.insert(&db, &1, &"uno".to_string()).unwrap();
+    assert!(matches!(txn.commit(), Err(TypedStoreError::Conflict)));
+}
+
+#[test]
+fn test_open_cf_descriptors() {
+    let path = tempfile::tempdir().unwrap();
+    let rocks = open_cf_descriptors(
+        path,
+        None,
+        &[
+            (
+                "hot_small_keys",
+                ColumnFamilyOptions::new().compression(rocksdb::DBCompressionType::None),
+            ),
+            (
+                "large_blobs",
+                ColumnFamilyOptions::new()
+                    .compression(rocksdb::DBCompressionType::Zstd)
+                    .fifo_compaction(64 * 1024 * 1024),
+            ),
+        ],
+    )
+    .expect("failed to open with per-cf descriptors");
+
+    let db: DBMap<i32, String> =
+        DBMap::reopen(&rocks, Some("hot_small_keys"), None).expect("failed to reopen");
+    db.insert(&1, &"one".to_string()).unwrap();
+    assert_eq!(db.get(&1).unwrap(), Some("one".to_string()));
+}
+
+#[test]
+fn test_merge() {
+    let path = tempfile::tempdir().unwrap();
+    let rocks = open_cf_descriptors(
+        path,
+        None,
+        &[(
+            "counters",
+            ColumnFamilyOptions::new().merge_operator::<i64, i64>(
+                "sum_i64",
+                |existing, operands| existing.unwrap_or(0) + operands.sum::<i64>(),
+            ),
+        )],
+    )
+    .expect("failed to open with a merge operator");
+
+    let db: DBMap<i32, i64> =
+        DBMap::reopen(&rocks, Some("counters"), None).expect("failed to reopen");
+
+    db.merge(&1, &3i64).unwrap();
+    db.merge(&1, &4i64).unwrap();
+    assert_eq!(db.get(&1).unwrap(), Some(7));
+
+    let batch = db
+        .batch()
+        .merge_batch(&db, vec![(1, 5i64), (2, 2i64)].into_iter())
+        .expect("failed to batch merge");
+    batch.write().expect("failed to write merge batch");
+
+    assert_eq!(db.get(&1).unwrap(), Some(12));
+    assert_eq!(db.get(&2).unwrap(), Some(2));
+}
+
+#[test]
+fn test_snapshot() {
+    let db = temp_db();
+    db.insert(&1, &"one".to_string()).unwrap();
+
+    let snapshot = db.snapshot();
+    db.insert(&1, &"uno".to_string()).unwrap();
+    db.insert(&2, &"two".to_string()).unwrap();
+
+    assert_eq!(snapshot.get(&1).unwrap(), Some("one".to_string()));
+    assert_eq!(snapshot.get(&2).unwrap(), None);
+    assert_eq!(
+        snapshot.multi_get(&[1, 2]).unwrap(),
+        vec![Some("one".to_string()), None]
+    );
+    assert_eq!(
+        snapshot.iter().collect::<Vec<_>>(),
+        vec![(1, "one".to_string())]
+    );
+
+    // The live map sees the writes made after the snapshot was taken.
+    assert_eq!(db.get(&1).unwrap(), Some("uno".to_string()));
+}
+
+#[test]
+fn test_snapshot_shared_across_maps() {
+    let path = tempfile::tempdir().unwrap();
+    let rocks = open_cf(&path, None, &["First_CF", "Second_CF"]).unwrap();
+    let db_cf_1: DBMap<i32, String> =
+        DBMap::reopen(&rocks, Some("First_CF"), None).expect("failed to reopen");
+    let db_cf_2: DBMap<i32, String> =
+        DBMap::reopen(&rocks, Some("Second_CF"), None).expect("failed to reopen");
+
+    db_cf_1.insert(&1, &"one".to_string()).unwrap();
+    db_cf_2.insert(&1, &"uno".to_string()).unwrap();
+
+    let shared = Arc::new(db_cf_1.rocksdb.snapshot());
+    db_cf_1.insert(&1, &"changed".to_string()).unwrap();
+
+    let view_1 = db_cf_1.snapshot_at(shared.clone()).expect("same database");
+    let view_2 = db_cf_2.snapshot_at(shared.clone()).expect("same database");
+    assert_eq!(view_1.get(&1).unwrap(), Some("one".to_string()));
+    assert_eq!(view_2.get(&1).unwrap(), Some("uno".to_string()));
+}
+
+#[test]
+fn test_snapshot_at_rejects_foreign_database() {
+    let db_a = temp_db();
+    let db_b = temp_db();
+
+    let foreign_snapshot = Arc::new(db_a.rocksdb.snapshot());
+    assert!(matches!(
+        db_b.snapshot_at(foreign_snapshot),
+        Err(TypedStoreError::CrossDBBatch)
+    ));
+}
+
+#[test]
+fn test_transaction_requires_transactional_db() {
+    let db = temp_db();
+    assert!(db.transaction().is_err());
+}
+
+#[derive(Default)]
+struct CountingMetricsSink {
+    latencies: std::sync::Mutex<Vec<(String, DBOperation)>>,
+    perf_contexts: std::sync::Mutex<Vec<(String, DBOperation, PerfContextMetrics)>>,
+}
+
+impl DBMetricsSink for CountingMetricsSink {
+    fn report_latency(&self, cf: &str, operation: DBOperation, _latency: std::time::Duration) {
+        self.latencies
+            .lock()
+            .unwrap()
+            .push((cf.to_string(), operation));
+    }
+
+    fn report_perf_context(&self, cf: &str, operation: DBOperation, metrics: PerfContextMetrics) {
+        self.perf_contexts
+            .lock()
+            .unwrap()
+            .push((cf.to_string(), operation, metrics));
+    }
+}
+
+#[test]
+fn test_metrics_report_latency() {
+    let sink = Arc::new(CountingMetricsSink::default());
+    let path = tempfile::tempdir().unwrap();
+    let db: DBMap<i32, String> = DBMap::open(
+        path,
+        None,
+        None,
+        Some(DBMapOptions {
+            metrics_sink: Some(sink.clone()),
+            perf_sample_rate: 0,
+        }),
+    )
+    .expect("failed to open test database");
+
+    db.insert(&1, &"one".to_string()).unwrap();
+    db.get(&1).unwrap();
+
+    let recorded = sink.latencies.lock().unwrap();
+    assert!(recorded.contains(&(rocksdb::DEFAULT_COLUMN_FAMILY_NAME.to_string(), DBOperation::Insert)));
+    assert!(recorded.contains(&(rocksdb::DEFAULT_COLUMN_FAMILY_NAME.to_string(), DBOperation::Get)));
+}
+
+#[test]
+fn test_metrics_report_perf_context() {
+    let sink = Arc::new(CountingMetricsSink::default());
+    let path = tempfile::tempdir().unwrap();
+    let db: DBMap<i32, String> = DBMap::open(
+        path,
+        None,
+        None,
+        Some(DBMapOptions {
+            metrics_sink: Some(sink.clone()),
+            // Sample every operation, so the assertions below are deterministic.
+            perf_sample_rate: 1,
+        }),
+    )
+    .expect("failed to open test database");
+
+    db.insert(&1, &"one".to_string()).unwrap();
+    // Nothing has been flushed, so this read is served from the memtable and
+    // its block counters are expected to be zero.
+    db.get(&1).unwrap();
+
+    let recorded = sink.perf_contexts.lock().unwrap();
+    let (cf, operation, metrics) = recorded
+        .iter()
+        .find(|(_, operation, _)| *operation == DBOperation::Get)
+        .expect("report_perf_context should have fired for the Get");
+    assert_eq!(cf, rocksdb::DEFAULT_COLUMN_FAMILY_NAME);
+    assert_eq!(*operation, DBOperation::Get);
+    // PerfContext counts should be plausible for a single point read: at
+    // most one block read/cache hit, not some impossible larger figure.
+    assert!(metrics.block_read_count <= 1);
+    assert!(metrics.block_cache_hit_count <= 1);
+}
+
+#[test]
+fn test_iter_range() {
+    let db = temp_db();
+    for i in 0..10 {
+        db.insert(&i, &i.to_string()).unwrap();
+    }
+
+    let collected: Vec<_> = db.iter_range(&3, &6).unwrap().collect();
+    assert_eq!(
+        collected,
+        (3..6).map(|i| (i, i.to_string())).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_safe_iter() {
+    let db = temp_db();
+    for i in 0..5 {
+        db.insert(&i, &i.to_string()).unwrap();
+    }
+
+    let collected: Result<Vec<_>, _> = db.safe_iter().collect();
+    let mut collected = collected.unwrap();
+    collected.sort();
+    assert_eq!(
+        collected,
+        (0..5).map(|i| (i, i.to_string())).collect::<Vec<_>>()
+    );
+}
+
+crate::reopen_store!(ExampleStore {
+    names: DBMap<u32, String>,
+    ages: DBMap<u32, u8>,
+});
+
+#[test]
+fn test_reopen_store_macro() {
+    let path = tempfile::tempdir().unwrap();
+    let store = ExampleStore::open(&path, None).expect("failed to open store");
+    assert_eq!(ExampleStore::COLUMN_FAMILIES, &["names", "ages"]);
+
+    store.names.insert(&1, &"alice".to_string()).unwrap();
+    store.ages.insert(&1, &30).unwrap();
+
+    // Reopening against the same on-disk database binds each field back to
+    // its own column family. The first handle must be closed first, since
+    // RocksDB holds a lock on the database directory while it is open.
+    drop(store);
+    let rocksdb = open_cf(&path, None, ExampleStore::COLUMN_FAMILIES).unwrap();
+    let reopened = ExampleStore::reopen(&rocksdb).expect("failed to reopen store");
+    assert_eq!(reopened.names.get(&1).unwrap(), Some("alice".to_string()));
+    assert_eq!(reopened.ages.get(&1).unwrap(), Some(30));
+}
+
+#[test]
+fn test_iter_from_reverse() {
+    let db = temp_db();
+    for i in 0..5 {
+        db.insert(&i, &i.to_string()).unwrap();
+    }
+
+    let collected: Vec<_> = db.iter_from(&3, Direction::Reverse).unwrap().collect();
+    assert_eq!(
+        collected,
+        vec![
+            (3, "3".to_string()),
+            (2, "2".to_string()),
+            (1, "1".to_string()),
+            (0, "0".to_string()),
+        ]
+    );
+}
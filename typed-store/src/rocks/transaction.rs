@@ -0,0 +1,133 @@
+// Copyright(C) 2021, Mysten Labs
+// SPDX-License-Identifier: Apache-2.0
+use bincode::Options;
+use rocksdb::MultiThreaded;
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
+
+use super::{RocksDB, TypedStoreError};
+use crate::rocks::DBMap;
+
+/// An optimistic read-modify-write transaction over one or more `DBMap`s
+/// sharing the same underlying database.
+///
+/// Reads performed through [`DBTransaction::get`] / [`DBTransaction::multi_get`]
+/// are tracked in the transaction's read set via RocksDB's `get_for_update`.
+/// `commit` fails with [`TypedStoreError::Conflict`] if any read key was
+/// modified by another writer since it was read, so callers should retry the
+/// whole read-modify-write on that error.
+pub struct DBTransaction<'a> {
+    rocksdb: Arc<rocksdb::OptimisticTransactionDB<MultiThreaded>>,
+    transaction: rocksdb::Transaction<'a, rocksdb::OptimisticTransactionDB<MultiThreaded>>,
+}
+
+impl<'a> DBTransaction<'a> {
+    pub(super) fn new(rocksdb: &'a Arc<rocksdb::OptimisticTransactionDB<MultiThreaded>>) -> Self {
+        let transaction = rocksdb.transaction();
+        DBTransaction {
+            rocksdb: rocksdb.clone(),
+            transaction,
+        }
+    }
+
+    /// Reads a key, adding it to the transaction's read set so that a
+    /// concurrent writer touching it will make this transaction's `commit`
+    /// fail with `TypedStoreError::Conflict`.
+    pub fn get<K: Serialize, V: DeserializeOwned>(
+        &self,
+        db: &DBMap<K, V>,
+        key: &K,
+    ) -> Result<Option<V>, TypedStoreError> {
+        if !db
+            .rocksdb
+            .ptr_eq(&RocksDB::OptimisticTransactionDB(self.rocksdb.clone()))
+        {
+            return Err(TypedStoreError::CrossDBBatch);
+        }
+
+        let config = bincode::DefaultOptions::new()
+            .with_big_endian()
+            .with_fixint_encoding();
+        let key_buf = config.serialize(key)?;
+
+        match self
+            .transaction
+            .get_for_update_cf(&db.cf(), &key_buf, /* exclusive */ true)?
+        {
+            Some(data) => Ok(Some(bincode::deserialize(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads a batch of keys, adding each to the transaction's read set.
+    pub fn multi_get<K: Serialize, V: DeserializeOwned>(
+        &self,
+        db: &DBMap<K, V>,
+        keys: &[K],
+    ) -> Result<Vec<Option<V>>, TypedStoreError> {
+        keys.iter().map(|key| self.get(db, key)).collect()
+    }
+
+    /// Stages an insert, to become visible to other readers only on `commit`.
+    pub fn insert<K: Serialize, V: Serialize>(
+        &self,
+        db: &DBMap<K, V>,
+        key: &K,
+        value: &V,
+    ) -> Result<(), TypedStoreError> {
+        if !db
+            .rocksdb
+            .ptr_eq(&RocksDB::OptimisticTransactionDB(self.rocksdb.clone()))
+        {
+            return Err(TypedStoreError::CrossDBBatch);
+        }
+
+        let config = bincode::DefaultOptions::new()
+            .with_big_endian()
+            .with_fixint_encoding();
+        let key_buf = config.serialize(key)?;
+        let value_buf = bincode::serialize(value)?;
+
+        self.transaction.put_cf(&db.cf(), key_buf, value_buf)?;
+        Ok(())
+    }
+
+    /// Stages a delete, to become visible to other readers only on `commit`.
+    pub fn delete<K: Serialize, V>(
+        &self,
+        db: &DBMap<K, V>,
+        key: &K,
+    ) -> Result<(), TypedStoreError> {
+        if !db
+            .rocksdb
+            .ptr_eq(&RocksDB::OptimisticTransactionDB(self.rocksdb.clone()))
+        {
+            return Err(TypedStoreError::CrossDBBatch);
+        }
+
+        let config = bincode::DefaultOptions::new()
+            .with_big_endian()
+            .with_fixint_encoding();
+        let key_buf = config.serialize(key)?;
+
+        self.transaction.delete_cf(&db.cf(), key_buf)?;
+        Ok(())
+    }
+
+    /// Attempts to commit the transaction. Returns `TypedStoreError::Conflict`
+    /// if a key in the read set was modified since it was read (`Busy`) or if
+    /// RocksDB's memtable history wasn't large enough to verify the read set
+    /// for conflicts (`TryAgain`) -- both indicate the same thing to a
+    /// caller: re-read, re-compute, and retry. `Expired` (lock held past a
+    /// transaction timeout) is a `TransactionDB`-only status and can't occur
+    /// against the `OptimisticTransactionDB` this type wraps.
+    pub fn commit(self) -> Result<(), TypedStoreError> {
+        self.transaction.commit().map_err(|err| {
+            if matches!(err.kind(), rocksdb::ErrorKind::Busy | rocksdb::ErrorKind::TryAgain) {
+                TypedStoreError::Conflict
+            } else {
+                TypedStoreError::from(err)
+            }
+        })
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright(C) 2021, Mysten Labs
+// SPDX-License-Identifier: Apache-2.0
+use serde::de::DeserializeOwned;
+use std::marker::PhantomData;
+
+use super::{DBRawIteratorMultiThreaded, Direction, TypedStoreError};
+
+/// An iterator over values in a column family.
+pub struct Values<'a, V> {
+    db_iter: DBRawIteratorMultiThreaded<'a>,
+    direction: Direction,
+    _phantom: PhantomData<V>,
+}
+
+impl<'a, V> Values<'a, V> {
+    pub(super) fn new(db_iter: DBRawIteratorMultiThreaded<'a>, direction: Direction) -> Self {
+        Self {
+            db_iter,
+            direction,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, V: DeserializeOwned> Iterator for Values<'a, V> {
+    type Item = V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.db_iter.valid() {
+            return None;
+        }
+
+        let value = self
+            .db_iter
+            .value()
+            .and_then(|v| bincode::deserialize(v).ok());
+
+        match self.direction {
+            Direction::Forward => self.db_iter.next(),
+            Direction::Reverse => self.db_iter.prev(),
+        }
+
+        value
+    }
+}
+
+/// A value iterator that surfaces RocksDB errors instead of treating them
+/// as end-of-stream; see `SafeIter` for why this is needed.
+pub struct SafeValues<'a, V> {
+    db_iter: DBRawIteratorMultiThreaded<'a>,
+    direction: Direction,
+    is_done: bool,
+    _phantom: PhantomData<V>,
+}
+
+impl<'a, V> SafeValues<'a, V> {
+    pub(super) fn new(db_iter: DBRawIteratorMultiThreaded<'a>, direction: Direction) -> Self {
+        Self {
+            db_iter,
+            direction,
+            is_done: false,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, V: DeserializeOwned> Iterator for SafeValues<'a, V> {
+    type Item = Result<V, TypedStoreError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None;
+        }
+
+        if !self.db_iter.valid() {
+            self.is_done = true;
+            return match self.db_iter.status() {
+                Err(err) => Some(Err(TypedStoreError::from(err))),
+                Ok(()) => None,
+            };
+        }
+
+        let result = bincode::deserialize(self.db_iter.value().expect("checked by `valid`"))
+            .map_err(TypedStoreError::from);
+
+        match self.direction {
+            Direction::Forward => self.db_iter.next(),
+            Direction::Reverse => self.db_iter.prev(),
+        }
+
+        Some(result)
+    }
+}
@@ -0,0 +1,42 @@
+// Copyright(C) 2021, Mysten Labs
+// SPDX-License-Identifier: Apache-2.0
+
+/// Trait describing the behavior of a map backed by a persistent store.
+///
+/// Implementors are expected to be cheaply `Clone`-able handles onto shared
+/// storage, so that the same underlying map can be used from multiple
+/// threads / tasks concurrently.
+pub trait Map<'a, K, V> {
+    type Error;
+    type Iterator: Iterator<Item = (K, V)>;
+    type Keys: Iterator<Item = K>;
+    type Values: Iterator<Item = V>;
+
+    /// Returns true if the map contains a value for the specified key.
+    fn contains_key(&self, key: &K) -> Result<bool, Self::Error>;
+
+    /// Returns the value for the given key, if it exists.
+    fn get(&self, key: &K) -> Result<Option<V>, Self::Error>;
+
+    /// Inserts the given key-value pair into the map.
+    fn insert(&self, key: &K, value: &V) -> Result<(), Self::Error>;
+
+    /// Removes the entry for the given key from the map.
+    fn remove(&self, key: &K) -> Result<(), Self::Error>;
+
+    /// Removes every key-value pair from the map.
+    fn clear(&self) -> Result<(), Self::Error>;
+
+    /// Returns an unbounded iterator visiting each key-value pair in the map.
+    fn iter(&'a self) -> Self::Iterator;
+
+    /// Returns an unbounded iterator visiting each key in the map.
+    fn keys(&'a self) -> Self::Keys;
+
+    /// Returns an unbounded iterator visiting each value in the map.
+    fn values(&'a self) -> Self::Values;
+
+    /// Returns the values for the given keys, preserving order and returning
+    /// `None` for keys that are not present in the map.
+    fn multi_get(&self, keys: &[K]) -> Result<Vec<Option<V>>, Self::Error>;
+}